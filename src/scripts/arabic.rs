@@ -6,16 +6,63 @@
 use crate::error::{ParseError, ShapingError};
 use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph};
 use crate::layout::{GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::tables::cmap::CmapSubtable;
 use crate::tag;
 
 use std::convert::From;
 use unicode_joining_type::{get_joining_type, JoiningType};
 
+/// Classification of a glyph produced by the `stch` (stretching/Syriac Abbreviation) feature.
+///
+/// HarfBuzz distinguishes the fixed end-caps of a stretched cluster from the glyph that is
+/// repeated to fill the available width. A later positioning pass uses this to know which
+/// glyphs it may duplicate.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StchAction {
+    /// The glyph is not part of a stretched cluster.
+    None,
+    /// The glyph is a fixed end-cap of a stretched cluster (the first or last glyph).
+    Fixed,
+    /// The glyph is the repeating interior component of a stretched cluster and may be
+    /// duplicated to fill the line width.
+    Repeating,
+}
+
+/// A kashida (U+0640 Tatweel) insertion opportunity at a glyph's connected position, analogous
+/// to HarfBuzz's per-glyph Arabic justification property. A justification/line-breaking layer
+/// inserts tatweel glyphs at the highest-priority opportunities first to stretch a line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JustificationPriority {
+    /// No kashida may be inserted at this position (e.g. a final/isolated letter, or a mark).
+    None,
+    /// A tatweel glyph itself; stretched directly rather than used as an insertion point.
+    Kashida,
+    /// A low-priority insertion point: a medial join whose letterform (e.g. Seen/Sad's tooth)
+    /// makes elongation here visually awkward, so other points should be exhausted first.
+    Low,
+    /// An insertion point after an initial join.
+    Medium,
+    /// The preferred insertion point: a medial join through a letter that elongates cleanly.
+    High,
+}
+
+/// The Unicode `Joining_Group` property, restricted to the groups needed to pick the correct
+/// Alaph final form when shaping Syriac (see [`gsub_apply_syriac`]). Other joining groups don't
+/// affect feature selection and are folded into `Other`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum JoiningGroup {
+    Other,
+    Alaph,
+    DalathRish,
+}
+
 #[derive(Clone)]
 struct ArabicData {
     joining_type: JoiningType,
     canonical_combining_class: u8,
     feature_tag: u32,
+    stch: StchAction,
+    joining_group: JoiningGroup,
 }
 
 impl GlyphData for ArabicData {
@@ -56,6 +103,18 @@ impl ArabicGlyph {
     fn set_feature_tag(&mut self, feature_tag: u32) {
         self.extra_data.feature_tag = feature_tag
     }
+
+    fn stch_action(&self) -> StchAction {
+        self.extra_data.stch
+    }
+
+    fn set_stch_action(&mut self, stch: StchAction) {
+        self.extra_data.stch = stch
+    }
+
+    fn joining_group(&self) -> JoiningGroup {
+        self.extra_data.joining_group
+    }
 }
 
 impl From<&RawGlyph<()>> for ArabicGlyph {
@@ -73,6 +132,11 @@ impl From<&RawGlyph<()>> for ArabicGlyph {
             GlyphOrigin::Direct => 0,
         };
 
+        let joining_group = match raw_glyph.glyph_origin {
+            GlyphOrigin::Char(c) => joining_group(c),
+            GlyphOrigin::Direct => JoiningGroup::Other,
+        };
+
         ArabicGlyph {
             unicodes: raw_glyph.unicodes.clone(),
             glyph_index: raw_glyph.glyph_index,
@@ -90,6 +154,8 @@ impl From<&RawGlyph<()>> for ArabicGlyph {
                 // For convenience, we loosely follow the spec (`2. Computing letter joining
                 // states`) here by initialising all `ArabicGlyph`s to `tag::ISOL`
                 feature_tag: tag::ISOL,
+                stch: StchAction::None,
+                joining_group,
             },
         }
     }
@@ -113,6 +179,16 @@ impl From<&ArabicGlyph> for RawGlyph<()> {
     }
 }
 
+/// Per-run outputs of [`gsub_apply_arabic`] beyond the shaped glyphs themselves, each parallel
+/// to the final `raw_glyphs` (one entry per glyph).
+#[derive(Clone, Debug, Default)]
+pub struct ArabicShapingData {
+    /// Classification of glyphs produced by the `stch` feature; see [`StchAction`].
+    pub stch: Vec<StchAction>,
+    /// Kashida insertion opportunities; see [`JustificationPriority`].
+    pub justification: Vec<JustificationPriority>,
+}
+
 pub fn gsub_apply_arabic(
     gsub_cache: &LayoutCache<GSUB>,
     gsub_table: &LayoutTable<GSUB>,
@@ -120,14 +196,28 @@ pub fn gsub_apply_arabic(
     script_tag: u32,
     lang_tag: Option<u32>,
     raw_glyphs: &mut Vec<RawGlyph<()>>,
-) -> Result<(), ShapingError> {
-    match gsub_table.find_script(script_tag)? {
-        Some(s) => {
-            if s.find_langsys_or_default(lang_tag)?.is_none() {
-                return Ok(());
-            }
-        }
-        None => return Ok(()),
+    reorder_marks: bool,
+    cmap_subtable: &CmapSubtable,
+    float_chars: &[char],
+) -> Result<ArabicShapingData, ShapingError> {
+    let has_gsub_coverage = match gsub_table.find_script(script_tag)? {
+        Some(s) => s.find_langsys_or_default(lang_tag)?.is_some(),
+        None => false,
+    };
+
+    if !has_gsub_coverage {
+        // The font has no Arabic layout tables (or none applicable to this script/language), so
+        // fall back to mapping nominal glyphs to their Unicode Arabic Presentation Forms using
+        // the cmap, which legacy/symbol fonts ship instead of GSUB rules. There's no GSUB `stch`
+        // lookup to run here, but kashida justification only needs the joining states this
+        // fallback already computes internally, so we still return real `justification` data.
+        let justification =
+            apply_presentation_form_fallback(raw_glyphs, cmap_subtable, float_chars)?;
+        let stch = vec![StchAction::None; raw_glyphs.len()];
+        return Ok(ArabicShapingData {
+            stch,
+            justification,
+        });
     }
 
     let arabic_glyphs = &mut raw_glyphs.iter().map(ArabicGlyph::from).collect();
@@ -147,34 +237,51 @@ pub fn gsub_apply_arabic(
 
     // 2. Computing letter joining states
 
-    {
-        let mut previous_i = arabic_glyphs
-            .iter()
-            .position(|g| !g.is_transparent())
-            .unwrap_or(0);
-
-        for i in (previous_i + 1)..arabic_glyphs.len() {
-            if arabic_glyphs[i].is_transparent() {
-                continue;
-            }
+    compute_joining_states(arabic_glyphs, float_chars);
 
-            if arabic_glyphs[previous_i].is_left_joining() && arabic_glyphs[i].is_right_joining() {
-                arabic_glyphs[i].set_feature_tag(tag::FINA);
-
-                match arabic_glyphs[previous_i].feature_tag() {
-                    tag::ISOL => arabic_glyphs[previous_i].set_feature_tag(tag::INIT),
-                    tag::FINA => arabic_glyphs[previous_i].set_feature_tag(tag::MEDI),
-                    _ => {}
-                }
-            }
+    // 6. Mark reordering
+    //
+    // Performed here, ahead of the GSUB feature application in steps 3-5, since lookups such as
+    // `stch`/`mkmk` expect marks to already be in their canonical shaping order. Gated behind
+    // `reorder_marks` so callers that want byte-for-byte parity with shapers that leave marks in
+    // input order can opt out.
 
-            previous_i = i;
-        }
+    if reorder_marks {
+        reorder_arabic_marks(arabic_glyphs);
     }
 
     // 3. Applying the stch feature
     //
-    // TODO hold off for future generalised solution (including the Syriac Abbreviation Mark)
+    // The `stch` feature substitutes an abbreviation mark (e.g. U+070F Syriac Abbreviation
+    // Mark) with a `MultipleSubst` sequence of glyphs that a line-layout pass stretches to fill
+    // the width of the text it marks. `gsub_apply_lookup` flags every glyph beyond the first
+    // produced by a `MultipleSubst` with `multi_subst_dup`, so we can recover the stch clusters
+    // afterwards: each cluster is a non-duplicate glyph followed by a run of such duplicates, and,
+    // HarfBuzz-style, the first and last glyph of each cluster are classified as `Fixed` and any
+    // glyphs in between as `Repeating`.
+    //
+    // Step 1's `CCMP` pass can itself contain `MultipleSubst` rules and so may have already set
+    // `multi_subst_dup` on some glyphs by the time we get here; that's intentional, since step 2
+    // (joining state computation) treats those decomposed glyphs as transparent too. Clear the
+    // flag now, right before applying `STCH`, so that `mark_stch_clusters` below only sees
+    // duplicates this lookup itself produced, rather than sweeping up unrelated CCMP output.
+
+    for glyph in arabic_glyphs.iter_mut() {
+        glyph.multi_subst_dup = false;
+    }
+
+    apply_lookups(
+        FeatureMask::STCH,
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        arabic_glyphs,
+        |_, _| true,
+    )?;
+
+    mark_stch_clusters(arabic_glyphs);
 
     // 4. Applying the language-form substitution features from GSUB
 
@@ -222,13 +329,577 @@ pub fn gsub_apply_arabic(
         )?;
     }
 
-    // 6. Mark reordering
-    //
-    // This is currently not implemented as results would then differ from other Arabic shapers
+    let stch = arabic_glyphs.iter().map(ArabicGlyph::stch_action).collect();
+    let justification = arabic_glyphs.iter().map(justification_priority).collect();
 
     *raw_glyphs = arabic_glyphs.iter().map(RawGlyph::from).collect();
 
-    Ok(())
+    Ok(ArabicShapingData {
+        stch,
+        justification,
+    })
+}
+
+/// Applies GSUB shaping for Syriac, sharing the `ArabicGlyph`/`ArabicData` machinery used for
+/// Arabic. Syriac joining is like Arabic's in most respects, but selecting the correct Alaph
+/// final form additionally requires the preceding base's `Joining_Group`, and Syriac fonts
+/// expose extra alternate forms (`med2`/`fin2`/`fin3`) that plain Arabic shaping never applies.
+///
+/// As with [`gsub_apply_arabic`], this also drives mark reordering, the `stch` feature (used by
+/// the Syriac Abbreviation Mark, U+070F) and kashida justification, returning the same
+/// [`ArabicShapingData`].
+pub fn gsub_apply_syriac(
+    gsub_cache: &LayoutCache<GSUB>,
+    gsub_table: &LayoutTable<GSUB>,
+    gdef_table: Option<&GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    raw_glyphs: &mut Vec<RawGlyph<()>>,
+    reorder_marks: bool,
+) -> Result<ArabicShapingData, ShapingError> {
+    let has_gsub_coverage = match gsub_table.find_script(script_tag)? {
+        Some(s) => s.find_langsys_or_default(lang_tag)?.is_some(),
+        None => false,
+    };
+
+    if !has_gsub_coverage {
+        // Glyphs are left unshaped, with no stch clusters or kashida opportunities reported.
+        return Ok(ArabicShapingData {
+            stch: vec![StchAction::None; raw_glyphs.len()],
+            justification: vec![JustificationPriority::None; raw_glyphs.len()],
+        });
+    }
+
+    let arabic_glyphs = &mut raw_glyphs.iter().map(ArabicGlyph::from).collect();
+
+    // 1. Compound character composition and decomposition
+
+    apply_lookups(
+        FeatureMask::CCMP,
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        arabic_glyphs,
+        |_, _| true,
+    )?;
+
+    // 2. Computing letter joining states, as for Arabic
+
+    compute_joining_states(arabic_glyphs, &[]);
+
+    // Alaph final-form selection: an Alaph in final position takes `fin2` when the preceding
+    // base is in the Dalath/Rish joining group, `fin3` when the preceding base joins on both
+    // sides (i.e. is dual-joining), and otherwise keeps the standard `fina` form.
+
+    apply_alaph_finals(arabic_glyphs);
+
+    // 6. Mark reordering, as for Arabic; see the comment on the equivalent step in
+    // `gsub_apply_arabic`.
+
+    if reorder_marks {
+        reorder_arabic_marks(arabic_glyphs);
+    }
+
+    // 3. Applying the stch feature, used by the Syriac Abbreviation Mark (U+070F); see the
+    // comment on the equivalent step in `gsub_apply_arabic`.
+
+    for glyph in arabic_glyphs.iter_mut() {
+        glyph.multi_subst_dup = false;
+    }
+
+    apply_lookups(
+        FeatureMask::STCH,
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        arabic_glyphs,
+        |_, _| true,
+    )?;
+
+    mark_stch_clusters(arabic_glyphs);
+
+    // 4. Applying the language-form substitution features from GSUB, including the Syriac-only
+    // `med2`/`fin2`/`fin3` alternate forms alongside the standard `isol`/`init`/`medi`/`fina`.
+
+    const SYRIAC_LANGUAGE_FEATURES: &'static [(FeatureMask, bool)] = &[
+        (FeatureMask::LOCL, true),
+        (FeatureMask::ISOL, false),
+        (FeatureMask::FINA, false),
+        (FeatureMask::FIN2, false),
+        (FeatureMask::FIN3, false),
+        (FeatureMask::MEDI, false),
+        (FeatureMask::MED2, false),
+        (FeatureMask::INIT, false),
+        (FeatureMask::RLIG, true),
+        (FeatureMask::RCLT, true),
+        (FeatureMask::CALT, true),
+    ];
+
+    for &(feature_mask, is_global) in SYRIAC_LANGUAGE_FEATURES {
+        apply_lookups(
+            feature_mask,
+            gsub_cache,
+            gsub_table,
+            gdef_table,
+            script_tag,
+            lang_tag,
+            arabic_glyphs,
+            |g, feature_tag| is_global || g.feature_tag() == feature_tag,
+        )?;
+    }
+
+    let stch = arabic_glyphs.iter().map(ArabicGlyph::stch_action).collect();
+    let justification = arabic_glyphs.iter().map(justification_priority).collect();
+
+    *raw_glyphs = arabic_glyphs.iter().map(RawGlyph::from).collect();
+
+    Ok(ArabicShapingData {
+        stch,
+        justification,
+    })
+}
+
+/// Overrides the generic `fina` tag set by [`compute_joining_states`] on Alaph glyphs in final
+/// position, per the Syriac-specific Alaph final-form rule.
+fn apply_alaph_finals(arabic_glyphs: &mut [ArabicGlyph]) {
+    const ALAPH: char = '\u{0710}';
+
+    for i in 1..arabic_glyphs.len() {
+        let is_final_alaph = arabic_glyphs[i].feature_tag() == tag::FINA
+            && matches!(arabic_glyphs[i].glyph_origin, GlyphOrigin::Char(ALAPH));
+
+        if !is_final_alaph {
+            continue;
+        }
+
+        let previous = match (0..i).rev().find(|&j| !arabic_glyphs[j].is_transparent()) {
+            Some(previous) => previous,
+            None => continue,
+        };
+
+        if arabic_glyphs[previous].joining_group() == JoiningGroup::DalathRish {
+            arabic_glyphs[i].set_feature_tag(tag::FIN2);
+        } else if arabic_glyphs[previous].is_left_joining()
+            && arabic_glyphs[previous].is_right_joining()
+        {
+            arabic_glyphs[i].set_feature_tag(tag::FIN3);
+        }
+    }
+}
+
+/// Derives a glyph's kashida insertion priority from its computed joining feature tag and
+/// letter identity, matching HarfBuzz's Arabic justification property.
+fn justification_priority(glyph: &ArabicGlyph) -> JustificationPriority {
+    const TATWEEL: char = '\u{0640}';
+
+    // Letters whose medial tooth shape makes a kashida inserted through them look awkward;
+    // other medial insertion points should be preferred over these.
+    const LOW_PRIORITY_MEDIALS: &[char] = &[
+        '\u{0633}', // Seen
+        '\u{0634}', // Sheen
+        '\u{0635}', // Sad
+        '\u{0636}', // Dad
+    ];
+
+    let ch = match glyph.glyph_origin {
+        GlyphOrigin::Char(c) => c,
+        GlyphOrigin::Direct => return JustificationPriority::None,
+    };
+
+    if ch == TATWEEL {
+        return JustificationPriority::Kashida;
+    }
+
+    match glyph.feature_tag() {
+        tag::MEDI if LOW_PRIORITY_MEDIALS.contains(&ch) => JustificationPriority::Low,
+        tag::MEDI => JustificationPriority::High,
+        tag::INIT => JustificationPriority::Medium,
+        _ => JustificationPriority::None,
+    }
+}
+
+/// Reorders runs of combining marks following an Arabic base glyph into ascending canonical
+/// combining class order, with the Shadda exception used by other Arabic shaping engines: a
+/// Shadda (CCC 33) sorts immediately after the base, ahead of any vowel mark of CCC 28-32,
+/// even though its CCC is numerically higher.
+fn reorder_arabic_marks(arabic_glyphs: &mut [ArabicGlyph]) {
+    let mut i = 0;
+    while i < arabic_glyphs.len() {
+        if arabic_glyphs[i].is_transparent() || arabic_glyphs[i].canonical_combining_class() != 0 {
+            i += 1;
+            continue;
+        }
+
+        // `i` is a base glyph (joining type not Transparent, CCC 0); sort the maximal run of
+        // marks that follows it, treating any CCC-0 mark as a barrier that resets the run.
+        let mut run_start = i + 1;
+        i = run_start;
+
+        while i < arabic_glyphs.len() && arabic_glyphs[i].is_transparent() {
+            if arabic_glyphs[i].canonical_combining_class() == 0 {
+                sort_mark_run(&mut arabic_glyphs[run_start..i]);
+                i += 1;
+                run_start = i;
+                continue;
+            }
+
+            i += 1;
+        }
+
+        sort_mark_run(&mut arabic_glyphs[run_start..i]);
+    }
+}
+
+fn sort_mark_run(run: &mut [ArabicGlyph]) {
+    // `sort_by_key` is a stable sort, so marks that tie on this key keep their input order.
+    run.sort_by_key(|g| mark_sort_key(g.canonical_combining_class()));
+}
+
+fn mark_sort_key(ccc: u8) -> (u8, u8) {
+    const SHADDA_CCC: u8 = 33;
+
+    if ccc == SHADDA_CCC {
+        (27, 1)
+    } else {
+        (ccc, 0)
+    }
+}
+
+/// Classifies each glyph produced by the `stch` feature as a fixed end-cap or a repeating
+/// interior component, so that a later positioning pass knows which glyphs it may duplicate to
+/// fill the line width occupied by the abbreviation.
+fn mark_stch_clusters(arabic_glyphs: &mut [ArabicGlyph]) {
+    let mut i = 0;
+    while i < arabic_glyphs.len() {
+        if arabic_glyphs[i].multi_subst_dup {
+            // A run of duplicates should always be preceded by the first glyph of the
+            // `MultipleSubst` output; guard against malformed input regardless.
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut end = i;
+        while end + 1 < arabic_glyphs.len() && arabic_glyphs[end + 1].multi_subst_dup {
+            end += 1;
+        }
+
+        if end > start {
+            arabic_glyphs[start].set_stch_action(StchAction::Fixed);
+            arabic_glyphs[end].set_stch_action(StchAction::Fixed);
+            for glyph in &mut arabic_glyphs[start + 1..end] {
+                glyph.set_stch_action(StchAction::Repeating);
+            }
+        }
+
+        i = end + 1;
+    }
+}
+
+/// Characters that real-world fonts (e.g. Amiri) expect to be shaping-transparent even though
+/// Unicode does not classify them as combining marks, so that the letters either side of them
+/// still join through them as if they weren't there. This set is not applied automatically:
+/// [`gsub_apply_arabic`] uses whatever `float_chars` its caller passes (this set, or a different
+/// one for fonts that position other characters the same way), and [`gsub_apply_syriac`] uses
+/// none.
+pub const DEFAULT_FLOAT_CHARS: &[char] = &[
+    '\u{0621}', // Hamza
+    '\u{0670}', // Letter Superscript Alef (combining dagger alef)
+    '\u{06E5}', // Small High Waw
+    '\u{06E6}', // Small High Yeh
+];
+
+/// Computes each glyph's joining feature tag (`isol`/`init`/`medi`/`fina`) from its Unicode
+/// joining type, per step 2 of the Arabic shaping model. Shared between the GSUB-driven path
+/// and the presentation-form fallback, both of which need this before choosing glyph forms.
+///
+/// `float_chars` lists codepoints that are skipped when determining adjacency between letters
+/// (as though they were transparent), without otherwise being treated as combining marks: they
+/// are still emitted in the output sequence and other passes (mark reordering, stch) are
+/// unaffected.
+fn compute_joining_states(arabic_glyphs: &mut [ArabicGlyph], float_chars: &[char]) {
+    let is_join_skippable = |g: &ArabicGlyph| g.is_transparent() || is_float(g, float_chars);
+
+    let mut previous_i = arabic_glyphs
+        .iter()
+        .position(|g| !is_join_skippable(g))
+        .unwrap_or(0);
+
+    for i in (previous_i + 1)..arabic_glyphs.len() {
+        if is_join_skippable(&arabic_glyphs[i]) {
+            continue;
+        }
+
+        if arabic_glyphs[previous_i].is_left_joining() && arabic_glyphs[i].is_right_joining() {
+            arabic_glyphs[i].set_feature_tag(tag::FINA);
+
+            match arabic_glyphs[previous_i].feature_tag() {
+                tag::ISOL => arabic_glyphs[previous_i].set_feature_tag(tag::INIT),
+                tag::FINA => arabic_glyphs[previous_i].set_feature_tag(tag::MEDI),
+                _ => {}
+            }
+        }
+
+        previous_i = i;
+    }
+}
+
+fn is_float(glyph: &ArabicGlyph, float_chars: &[char]) -> bool {
+    match glyph.glyph_origin {
+        GlyphOrigin::Char(c) => float_chars.contains(&c),
+        GlyphOrigin::Direct => false,
+    }
+}
+
+/// Fallback shaping for fonts that ship Unicode Arabic Presentation Forms but no Arabic GSUB
+/// rules. Maps each basic Arabic letter (U+0621-U+063A, U+0641-U+064A) to the presentation form
+/// glyph (FE80-FEFC) matching its computed joining feature tag, handling the lam-alef ligature
+/// specially since it has no nominal-character encoding of its own. Other characters, including
+/// letters whose forms lie in FB50-FDFF, keep their nominal glyph.
+///
+/// Also returns each output glyph's kashida [`JustificationPriority`], computed from the same
+/// joining states this function derives internally; callers have no other way to get at them
+/// since `raw_glyphs` is replaced with plain `RawGlyph<()>`s here. A lam-alef ligature gets
+/// `JustificationPriority::None`: the join between lam and alef is inside the ligature, and alef
+/// does not join to the left.
+fn apply_presentation_form_fallback(
+    raw_glyphs: &mut Vec<RawGlyph<()>>,
+    cmap_subtable: &CmapSubtable,
+    float_chars: &[char],
+) -> Result<Vec<JustificationPriority>, ShapingError> {
+    let mut arabic_glyphs: Vec<ArabicGlyph> = raw_glyphs.iter().map(ArabicGlyph::from).collect();
+    compute_joining_states(&mut arabic_glyphs, float_chars);
+
+    let mut shaped = Vec::with_capacity(arabic_glyphs.len());
+    let mut justification = Vec::with_capacity(arabic_glyphs.len());
+    let mut i = 0;
+
+    while i < arabic_glyphs.len() {
+        if let Some(next) = arabic_glyphs.get(i + 1) {
+            if let (GlyphOrigin::Char('\u{0644}'), GlyphOrigin::Char(alef)) =
+                (arabic_glyphs[i].glyph_origin, next.glyph_origin)
+            {
+                if let Some((isol, fina)) = lam_alef_ligature_form(alef) {
+                    let lam_starts_connection =
+                        matches!(arabic_glyphs[i].feature_tag(), tag::ISOL | tag::INIT);
+                    let form = if lam_starts_connection { isol } else { fina };
+
+                    if let Some(glyph_index) = cmap_subtable.map_glyph(form as u32)? {
+                        let mut ligature = RawGlyph::from(&arabic_glyphs[i]);
+                        ligature.glyph_index = glyph_index;
+                        ligature.unicodes.extend(next.unicodes.iter().cloned());
+                        shaped.push(ligature);
+                        justification.push(JustificationPriority::None);
+                        i += 2;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let mut glyph = RawGlyph::from(&arabic_glyphs[i]);
+        if let GlyphOrigin::Char(ch) = arabic_glyphs[i].glyph_origin {
+            if let Some(form) = presentation_form(ch, arabic_glyphs[i].feature_tag()) {
+                if let Some(glyph_index) = cmap_subtable.map_glyph(form as u32)? {
+                    glyph.glyph_index = glyph_index;
+                }
+            }
+        }
+
+        shaped.push(glyph);
+        justification.push(justification_priority(&arabic_glyphs[i]));
+        i += 1;
+    }
+
+    *raw_glyphs = shaped;
+
+    Ok(justification)
+}
+
+/// Returns the isolated and final Unicode presentation-form codepoints for the lam-alef
+/// ligature formed by lam (U+0644) followed by `alef`, if `alef` is one of the four alef
+/// variants that ligate with lam.
+fn lam_alef_ligature_form(alef: char) -> Option<(char, char)> {
+    match alef {
+        '\u{0622}' => Some(('\u{FEF5}', '\u{FEF6}')), // Alef with Madda Above
+        '\u{0623}' => Some(('\u{FEF7}', '\u{FEF8}')), // Alef with Hamza Above
+        '\u{0625}' => Some(('\u{FEF9}', '\u{FEFA}')), // Alef with Hamza Below
+        '\u{0627}' => Some(('\u{FEFB}', '\u{FEFC}')), // Plain Alef
+        _ => None,
+    }
+}
+
+/// Returns the Unicode Arabic Presentation Form codepoint for `ch` shaped in the position named
+/// by `feature_tag` (one of `tag::ISOL`/`INIT`/`MEDI`/`FINA`), or `None` if `ch` is not one of
+/// the basic Arabic letters listed below. Where `ch` has no form for that position (e.g.
+/// right-joining-only letters have no initial/medial forms), its isolated form is returned.
+fn presentation_form(ch: char, feature_tag: u32) -> Option<char> {
+    // (isolated, final, initial, medial); `None` where the letter doesn't join on that side.
+    let forms: (char, Option<char>, Option<char>, Option<char>) = match ch {
+        '\u{0621}' => ('\u{FE80}', None, None, None), // Hamza (non-joining)
+        '\u{0622}' => ('\u{FE81}', Some('\u{FE82}'), None, None), // Alef with Madda Above
+        '\u{0623}' => ('\u{FE83}', Some('\u{FE84}'), None, None), // Alef with Hamza Above
+        '\u{0624}' => ('\u{FE85}', Some('\u{FE86}'), None, None), // Waw with Hamza Above
+        '\u{0625}' => ('\u{FE87}', Some('\u{FE88}'), None, None), // Alef with Hamza Below
+        '\u{0626}' => (
+            '\u{FE89}',
+            Some('\u{FE8A}'),
+            Some('\u{FE8B}'),
+            Some('\u{FE8C}'),
+        ), // Yeh with Hamza Above
+        '\u{0627}' => ('\u{FE8D}', Some('\u{FE8E}'), None, None), // Alef
+        '\u{0628}' => (
+            '\u{FE8F}',
+            Some('\u{FE90}'),
+            Some('\u{FE91}'),
+            Some('\u{FE92}'),
+        ), // Beh
+        '\u{0629}' => ('\u{FE93}', Some('\u{FE94}'), None, None), // Teh Marbuta
+        '\u{062A}' => (
+            '\u{FE95}',
+            Some('\u{FE96}'),
+            Some('\u{FE97}'),
+            Some('\u{FE98}'),
+        ), // Teh
+        '\u{062B}' => (
+            '\u{FE99}',
+            Some('\u{FE9A}'),
+            Some('\u{FE9B}'),
+            Some('\u{FE9C}'),
+        ), // Theh
+        '\u{062C}' => (
+            '\u{FE9D}',
+            Some('\u{FE9E}'),
+            Some('\u{FE9F}'),
+            Some('\u{FEA0}'),
+        ), // Jeem
+        '\u{062D}' => (
+            '\u{FEA1}',
+            Some('\u{FEA2}'),
+            Some('\u{FEA3}'),
+            Some('\u{FEA4}'),
+        ), // Hah
+        '\u{062E}' => (
+            '\u{FEA5}',
+            Some('\u{FEA6}'),
+            Some('\u{FEA7}'),
+            Some('\u{FEA8}'),
+        ), // Khah
+        '\u{062F}' => ('\u{FEA9}', Some('\u{FEAA}'), None, None), // Dal
+        '\u{0630}' => ('\u{FEAB}', Some('\u{FEAC}'), None, None), // Thal
+        '\u{0631}' => ('\u{FEAD}', Some('\u{FEAE}'), None, None), // Reh
+        '\u{0632}' => ('\u{FEAF}', Some('\u{FEB0}'), None, None), // Zain
+        '\u{0633}' => (
+            '\u{FEB1}',
+            Some('\u{FEB2}'),
+            Some('\u{FEB3}'),
+            Some('\u{FEB4}'),
+        ), // Seen
+        '\u{0634}' => (
+            '\u{FEB5}',
+            Some('\u{FEB6}'),
+            Some('\u{FEB7}'),
+            Some('\u{FEB8}'),
+        ), // Sheen
+        '\u{0635}' => (
+            '\u{FEB9}',
+            Some('\u{FEBA}'),
+            Some('\u{FEBB}'),
+            Some('\u{FEBC}'),
+        ), // Sad
+        '\u{0636}' => (
+            '\u{FEBD}',
+            Some('\u{FEBE}'),
+            Some('\u{FEBF}'),
+            Some('\u{FEC0}'),
+        ), // Dad
+        '\u{0637}' => (
+            '\u{FEC1}',
+            Some('\u{FEC2}'),
+            Some('\u{FEC3}'),
+            Some('\u{FEC4}'),
+        ), // Tah
+        '\u{0638}' => (
+            '\u{FEC5}',
+            Some('\u{FEC6}'),
+            Some('\u{FEC7}'),
+            Some('\u{FEC8}'),
+        ), // Zah
+        '\u{0639}' => (
+            '\u{FEC9}',
+            Some('\u{FECA}'),
+            Some('\u{FECB}'),
+            Some('\u{FECC}'),
+        ), // Ain
+        '\u{063A}' => (
+            '\u{FECD}',
+            Some('\u{FECE}'),
+            Some('\u{FECF}'),
+            Some('\u{FED0}'),
+        ), // Ghain
+        '\u{0641}' => (
+            '\u{FED1}',
+            Some('\u{FED2}'),
+            Some('\u{FED3}'),
+            Some('\u{FED4}'),
+        ), // Feh
+        '\u{0642}' => (
+            '\u{FED5}',
+            Some('\u{FED6}'),
+            Some('\u{FED7}'),
+            Some('\u{FED8}'),
+        ), // Qaf
+        '\u{0643}' => (
+            '\u{FED9}',
+            Some('\u{FEDA}'),
+            Some('\u{FEDB}'),
+            Some('\u{FEDC}'),
+        ), // Kaf
+        '\u{0644}' => (
+            '\u{FEDD}',
+            Some('\u{FEDE}'),
+            Some('\u{FEDF}'),
+            Some('\u{FEE0}'),
+        ), // Lam
+        '\u{0645}' => (
+            '\u{FEE1}',
+            Some('\u{FEE2}'),
+            Some('\u{FEE3}'),
+            Some('\u{FEE4}'),
+        ), // Meem
+        '\u{0646}' => (
+            '\u{FEE5}',
+            Some('\u{FEE6}'),
+            Some('\u{FEE7}'),
+            Some('\u{FEE8}'),
+        ), // Noon
+        '\u{0647}' => (
+            '\u{FEE9}',
+            Some('\u{FEEA}'),
+            Some('\u{FEEB}'),
+            Some('\u{FEEC}'),
+        ), // Heh
+        '\u{0648}' => ('\u{FEED}', Some('\u{FEEE}'), None, None), // Waw
+        '\u{0649}' => ('\u{FEEF}', Some('\u{FEF0}'), None, None), // Alef Maksura
+        '\u{064A}' => (
+            '\u{FEF1}',
+            Some('\u{FEF2}'),
+            Some('\u{FEF3}'),
+            Some('\u{FEF4}'),
+        ), // Yeh
+        _ => return None,
+    };
+
+    match feature_tag {
+        tag::FINA => forms.1.or(Some(forms.0)),
+        tag::INIT => forms.2.or(Some(forms.0)),
+        tag::MEDI => forms.3.or(Some(forms.0)),
+        _ => Some(forms.0),
+    }
 }
 
 fn apply_lookups(
@@ -262,6 +933,18 @@ fn apply_lookups(
     Ok(())
 }
 
+/// Looks up the Unicode `Joining_Group` property for `ch`, restricted to the Alaph and
+/// Dalath/Rish groups needed by [`gsub_apply_syriac`]; every other character is `Other`.
+fn joining_group(ch: char) -> JoiningGroup {
+    match ch {
+        '\u{0710}' => JoiningGroup::Alaph,      // Syriac Letter Alaph
+        '\u{0715}' => JoiningGroup::DalathRish, // Syriac Letter Dalath
+        '\u{0716}' => JoiningGroup::DalathRish, // Syriac Letter Dotless Dalath Rish
+        '\u{072A}' => JoiningGroup::DalathRish, // Syriac Letter Rish
+        _ => JoiningGroup::Other,
+    }
+}
+
 fn canonical_combining_class(ch: char) -> u8 {
     match ch {
         '\u{064B}' => 27,  // Fathatan
@@ -363,3 +1046,166 @@ fn canonical_combining_class(ch: char) -> u8 {
         _ => 0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn char_glyph(ch: char) -> ArabicGlyph {
+        let raw_glyph = RawGlyph {
+            unicodes: std::iter::once(ch).collect(),
+            glyph_index: 0,
+            liga_component_pos: 0,
+            glyph_origin: GlyphOrigin::Char(ch),
+            small_caps: false,
+            multi_subst_dup: false,
+            is_vert_alt: false,
+            fake_bold: false,
+            fake_italic: false,
+            variation: None,
+            extra_data: (),
+        };
+
+        ArabicGlyph::from(&raw_glyph)
+    }
+
+    #[test]
+    fn joining_group_restricted_to_alaph_and_dalath_rish() {
+        assert_eq!(joining_group('\u{0710}'), JoiningGroup::Alaph); // Alaph
+        assert_eq!(joining_group('\u{0715}'), JoiningGroup::DalathRish); // Dalath
+        assert_eq!(joining_group('\u{0716}'), JoiningGroup::DalathRish); // Dotless Dalath Rish
+        assert_eq!(joining_group('\u{072A}'), JoiningGroup::DalathRish); // Rish
+
+        // Gamal Garshuni looks similar but belongs to the Gamal group, not Dalath/Rish.
+        assert_eq!(joining_group('\u{0714}'), JoiningGroup::Other);
+        assert_eq!(joining_group('\u{0712}'), JoiningGroup::Other); // Beth
+    }
+
+    #[test]
+    fn mark_sort_key_places_shadda_ahead_of_higher_ccc_marks() {
+        const FATHA_CCC: u8 = 30;
+        const SHADDA_CCC: u8 = 33;
+
+        assert!(mark_sort_key(SHADDA_CCC) < mark_sort_key(FATHA_CCC));
+    }
+
+    #[test]
+    fn reorder_arabic_marks_sorts_shadda_ahead_of_fatha() {
+        let mut glyphs = vec![
+            char_glyph('\u{0644}'), // Lam (base)
+            char_glyph('\u{064E}'), // Fatha (CCC 30)
+            char_glyph('\u{0651}'), // Shadda (CCC 33)
+        ];
+
+        reorder_arabic_marks(&mut glyphs);
+
+        assert!(matches!(glyphs[1].glyph_origin, GlyphOrigin::Char('\u{0651}')));
+        assert!(matches!(glyphs[2].glyph_origin, GlyphOrigin::Char('\u{064E}')));
+    }
+
+    #[test]
+    fn reorder_arabic_marks_does_not_merge_across_a_ccc_zero_mark() {
+        let mut glyphs = vec![
+            char_glyph('\u{0644}'), // Lam (base)
+            char_glyph('\u{064E}'), // Fatha (CCC 30)
+            char_glyph('\u{0644}'), // forced transparent, CCC 0: resets the run
+            char_glyph('\u{0651}'), // Shadda (CCC 33)
+        ];
+        glyphs[2].multi_subst_dup = true;
+
+        reorder_arabic_marks(&mut glyphs);
+
+        // Without the CCC-0 reset, the Shadda at the end would sort ahead of the Fatha. With it,
+        // each single-glyph sub-run is a no-op and the input order is preserved.
+        assert!(matches!(glyphs[1].glyph_origin, GlyphOrigin::Char('\u{064E}')));
+        assert!(matches!(glyphs[3].glyph_origin, GlyphOrigin::Char('\u{0651}')));
+    }
+
+    #[test]
+    fn mark_stch_clusters_classifies_fixed_end_caps_and_repeating_interior() {
+        let mut glyphs = vec![
+            char_glyph('\u{070F}'), // first glyph of the MultipleSubst output
+            char_glyph('\u{070F}'),
+            char_glyph('\u{070F}'),
+            char_glyph('\u{070F}'), // unrelated glyph, not part of any MultipleSubst run
+        ];
+        glyphs[1].multi_subst_dup = true;
+        glyphs[2].multi_subst_dup = true;
+
+        mark_stch_clusters(&mut glyphs);
+
+        assert_eq!(glyphs[0].stch_action(), StchAction::Fixed);
+        assert_eq!(glyphs[1].stch_action(), StchAction::Repeating);
+        assert_eq!(glyphs[2].stch_action(), StchAction::Fixed);
+        assert_eq!(glyphs[3].stch_action(), StchAction::None);
+    }
+
+    #[test]
+    fn justification_priority_for_tatweel_and_medial_letters() {
+        let tatweel = char_glyph('\u{0640}');
+        assert_eq!(justification_priority(&tatweel), JustificationPriority::Kashida);
+
+        let mut seen = char_glyph('\u{0633}'); // Seen: low-priority medial
+        seen.set_feature_tag(tag::MEDI);
+        assert_eq!(justification_priority(&seen), JustificationPriority::Low);
+
+        let mut beh = char_glyph('\u{0628}'); // Beh: ordinary medial
+        beh.set_feature_tag(tag::MEDI);
+        assert_eq!(justification_priority(&beh), JustificationPriority::High);
+
+        let mut beh_init = char_glyph('\u{0628}');
+        beh_init.set_feature_tag(tag::INIT);
+        assert_eq!(justification_priority(&beh_init), JustificationPriority::Medium);
+
+        let mut beh_isol = char_glyph('\u{0628}');
+        beh_isol.set_feature_tag(tag::ISOL);
+        assert_eq!(justification_priority(&beh_isol), JustificationPriority::None);
+    }
+
+    #[test]
+    fn lam_alef_ligature_form_only_matches_the_four_alef_variants() {
+        assert_eq!(
+            lam_alef_ligature_form('\u{0627}'), // Plain Alef
+            Some(('\u{FEFB}', '\u{FEFC}'))
+        );
+        assert_eq!(lam_alef_ligature_form('\u{0628}'), None); // Beh does not ligate with Lam
+    }
+
+    #[test]
+    fn presentation_form_falls_back_to_isolated_when_a_position_has_no_form() {
+        // Hamza is non-joining, so it has no final/initial/medial form; every position falls
+        // back to its isolated presentation form.
+        assert_eq!(presentation_form('\u{0621}', tag::FINA), Some('\u{FE80}'));
+        assert_eq!(presentation_form('\u{0621}', tag::ISOL), Some('\u{FE80}'));
+
+        assert_eq!(presentation_form('\u{0628}', tag::FINA), Some('\u{FE90}')); // Beh final
+
+        assert_eq!(presentation_form('a', tag::ISOL), None);
+    }
+
+    #[test]
+    fn apply_alaph_finals_picks_fin2_after_a_dalath_rish_base() {
+        let mut glyphs = vec![
+            char_glyph('\u{0715}'), // Dalath
+            char_glyph('\u{0710}'), // Alaph
+        ];
+        glyphs[1].set_feature_tag(tag::FINA);
+
+        apply_alaph_finals(&mut glyphs);
+
+        assert_eq!(glyphs[1].feature_tag(), tag::FIN2);
+    }
+
+    #[test]
+    fn apply_alaph_finals_picks_fin3_after_a_dual_joining_non_dalath_rish_base() {
+        let mut glyphs = vec![
+            char_glyph('\u{0712}'), // Beth: dual-joining, not Dalath/Rish
+            char_glyph('\u{0710}'), // Alaph
+        ];
+        glyphs[1].set_feature_tag(tag::FINA);
+
+        apply_alaph_finals(&mut glyphs);
+
+        assert_eq!(glyphs[1].feature_tag(), tag::FIN3);
+    }
+}